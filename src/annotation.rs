@@ -1,16 +1,85 @@
 use chrono::{Local, TimeZone, Utc};
+use std::collections::BTreeMap;
 use std::fmt::{self, Display, Formatter};
+use std::path::PathBuf;
+use std::str::FromStr;
+
+/// The severity of an [`Annotation`], driving its color and marker in the TUI.
+///
+/// Variants are listed from least to most severe, matching the order the TUI's sort-by-level view
+/// ranks them in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum AnnotationType {
+    /// A plain, informational note. The default level for annotations that don't specify one.
+    #[default]
+    Note,
+
+    /// A note that highlights useful context, without indicating a problem.
+    Info,
+
+    /// A note that flags something that should probably be addressed.
+    Warning,
+
+    /// A note that flags something broken or incorrect.
+    Error,
+}
+
+impl Display for AnnotationType {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            AnnotationType::Note => "note",
+            AnnotationType::Info => "info",
+            AnnotationType::Warning => "warning",
+            AnnotationType::Error => "error",
+        };
+
+        write!(f, "{}", name)
+    }
+}
+
+impl FromStr for AnnotationType {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "note" => Ok(AnnotationType::Note),
+            "info" => Ok(AnnotationType::Info),
+            "warning" => Ok(AnnotationType::Warning),
+            "error" => Ok(AnnotationType::Error),
+            other => Err(format!("Unknown annotation level: '{}'", other)),
+        }
+    }
+}
 
 /// Represents an annotation with content and a timestamp.
 ///
 /// This struct represents an annotation with a textual content and a timestamp indicating when the
 /// annotation was created. The timestamp is measured in milliseconds since the Unix epoch.
+#[derive(Debug)]
 pub struct Annotation {
     /// The textual content of the annotation.
     pub content: String,
 
     /// The timestamp when the annotation was created, measured in milliseconds since the Unix epoch.
     pub created_at: u64,
+
+    /// Arbitrary `key=value` tags attached to the annotation, such as `project=jotspot`.
+    ///
+    /// Tags are stored in a [`BTreeMap`] so that serialized annotations have a stable, deterministic
+    /// ordering of their tags.
+    pub tags: BTreeMap<String, String>,
+
+    /// The source file this annotation is anchored to, if any.
+    pub origin: Option<PathBuf>,
+
+    /// The 1-based line number within `origin` that this annotation is anchored to.
+    pub line_start: Option<usize>,
+
+    /// The `(start, end)` char range within the anchored line that this annotation highlights.
+    pub range: Option<(usize, usize)>,
+
+    /// The severity of this annotation, defaulting to [`AnnotationType::Note`].
+    pub level: AnnotationType,
 }
 
 /// Data structure for storing and managing annotations.
@@ -37,8 +106,8 @@ impl AnnotationsData {
     /// ```
     /// // Create an AnnotationsData instance with initial annotations.
     /// let annotations = vec![
-    ///     Annotation::new("First annotation"),
-    ///     Annotation::new("Second annotation"),
+    ///     Annotation::new_with_metadata("First annotation", BTreeMap::new(), None, AnnotationType::default()),
+    ///     Annotation::new_with_metadata("Second annotation", BTreeMap::new(), None, AnnotationType::default()),
     /// ];
     /// let mut annotations_data = AnnotationsData::new(annotations);
     /// ```
@@ -57,8 +126,8 @@ impl AnnotationsData {
     /// ```
     /// // Create an AnnotationsData instance with initial annotations.
     /// let annotations = vec![
-    ///     Annotation::new("First annotation"),
-    ///     Annotation::new("Second annotation"),
+    ///     Annotation::new_with_metadata("First annotation", BTreeMap::new(), None, AnnotationType::default()),
+    ///     Annotation::new_with_metadata("Second annotation", BTreeMap::new(), None, AnnotationType::default()),
     /// ];
     /// let annotations_data = AnnotationsData::new(annotations);
     ///
@@ -81,8 +150,8 @@ impl AnnotationsData {
     /// ```
     /// // Create an AnnotationsData instance with initial annotations.
     /// let annotations = vec![
-    ///     Annotation::new("First annotation"),
-    ///     Annotation::new("Second annotation"),
+    ///     Annotation::new_with_metadata("First annotation", BTreeMap::new(), None, AnnotationType::default()),
+    ///     Annotation::new_with_metadata("Second annotation", BTreeMap::new(), None, AnnotationType::default()),
     /// ];
     /// let mut annotations_data = AnnotationsData::new(annotations);
     ///
@@ -95,22 +164,56 @@ impl AnnotationsData {
 }
 
 impl Annotation {
-    /// Creates a new annotation with the given content.
+    /// Creates a new annotation, optionally attaching tags, a source anchor, and a severity
+    /// level, and stamps `created_at` with the current local time.
     ///
-    /// This function generates a new annotation instance with the provided content and sets
-    /// the `created_at` timestamp to the current local time.
+    /// This is the single constructor behind every annotation `jotspot` creates, whether from
+    /// `--tag`, `--at`, `--level`, any combination of them, or none at all: callers that only
+    /// need content pass `BTreeMap::new()`, `None`, and `AnnotationType::default()` for the rest
+    /// rather than picking from a family of single-purpose constructors.
     ///
     /// # Arguments
     ///
-    /// - `content`:  The content of the annotation.
+    /// - `content`: The content of the annotation.
+    /// - `tags`: The `key=value` tags to attach to the annotation.
+    /// - `anchor`: The `(origin, line_start, range)` source location to anchor the annotation to,
+    ///   if any.
+    /// - `level`: The severity of the annotation.
     ///
     /// # Returns
     ///
     /// A new [`Annotation`] instance.
-    pub fn new(content: &str) -> Self {
+    pub fn new_with_metadata(
+        content: &str,
+        tags: BTreeMap<String, String>,
+        anchor: Option<(PathBuf, usize, (usize, usize))>,
+        level: AnnotationType,
+    ) -> Self {
+        let (origin, line_start, range) = match anchor {
+            Some((origin, line_start, range)) => (Some(origin), Some(line_start), Some(range)),
+            None => (None, None, None),
+        };
+
+        Self::build(content, tags, origin, line_start, range, level)
+    }
+
+    /// Builds an annotation from its parts, stamping `created_at` with the current local time.
+    fn build(
+        content: &str,
+        tags: BTreeMap<String, String>,
+        origin: Option<PathBuf>,
+        line_start: Option<usize>,
+        range: Option<(usize, usize)>,
+        level: AnnotationType,
+    ) -> Self {
         Self {
             content: content.to_string(),
             created_at: Local::now().timestamp_millis() as u64,
+            tags,
+            origin,
+            line_start,
+            range,
+            level,
         }
     }
 
@@ -180,28 +283,331 @@ impl Annotation {
     }
 }
 
-impl From<&str> for Annotation {
-    fn from(string: &str) -> Annotation {
-        let created_at_delim_pos = string
-            .find(' ')
-            .expect("Unable to find the 'created_at' delimiter position in the string.");
+/// An error encountered while parsing a single line of the `.annotations` file.
+///
+/// This carries enough information to render the offending line as a caret-annotated snippet
+/// instead of panicking, so a damaged notes file can describe itself rather than aborting the
+/// whole TUI session.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    /// The 1-based line number within the `.annotations` file where the error occurred.
+    pub line: usize,
+
+    /// The `(start, end)` byte range of the offending token within that line.
+    pub range: (usize, usize),
+
+    /// A human-readable description of what was expected.
+    pub message: String,
+}
 
-        let (created_at_str, content_str) = string.split_at(created_at_delim_pos);
+impl ParseError {
+    /// Creates a new [`ParseError`] for the given byte `range` and `message`, with `line` left at
+    /// `0` until [`ParseError::with_line`] fills it in once the offending line number is known.
+    pub(crate) fn new(range: (usize, usize), message: impl Into<String>) -> Self {
+        Self {
+            line: 0,
+            range,
+            message: message.into(),
+        }
+    }
+
+    /// Returns this [`ParseError`] with its `line` set to the 1-based line number it came from.
+    pub fn with_line(mut self, line: usize) -> Self {
+        self.line = line;
+        self
+    }
+}
+
+/// Escapes backslashes, tabs and newlines in `content` so they can't be mistaken for the line
+/// format's `\t`-delimited metadata section or the newline between annotations.
+///
+/// [`unescape_line_content`] reverses this when the line is parsed back.
+fn escape_line_content(content: &str) -> String {
+    content.replace('\\', "\\\\").replace('\t', "\\t").replace('\n', "\\n")
+}
+
+/// Reverses [`escape_line_content`], turning `\\`, `\t` and `\n` escape sequences back into their
+/// literal characters.
+fn unescape_line_content(content: &str) -> String {
+    let mut result = String::with_capacity(content.len());
+    let mut chars = content.chars();
+
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            result.push(c);
+            continue;
+        }
+
+        match chars.next() {
+            Some('t') => result.push('\t'),
+            Some('n') => result.push('\n'),
+            Some('\\') => result.push('\\'),
+            Some(other) => {
+                result.push('\\');
+                result.push(other);
+            }
+            None => result.push('\\'),
+        }
+    }
+
+    result
+}
+
+impl TryFrom<&str> for Annotation {
+    type Error = ParseError;
+
+    fn try_from(string: &str) -> Result<Self, Self::Error> {
+        const EXPECTED_FORMAT: &str = "expected `<timestamp> <content>`";
+
+        // The tags section, if present, is appended after the `<timestamp> <content>` pair as a
+        // series of `\t`-delimited `key=value` tokens, so it must be split off before the content
+        // is parsed.
+        let mut parts = string.split('\t');
+        let header = parts.next().unwrap_or("");
+
+        let Some(created_at_delim_pos) = header.find(' ') else {
+            return Err(ParseError::new((0, header.len()), EXPECTED_FORMAT));
+        };
+
+        let (created_at_str, content_str) = header.split_at(created_at_delim_pos);
         let content_str = &content_str[1..]; // Skip the space
 
-        let created_at = created_at_str
-            .parse()
-            .expect("From<&str> for Annotation: created_at could not be parsed.");
+        let Ok(created_at) = created_at_str.parse() else {
+            return Err(ParseError::new((0, created_at_str.len()), EXPECTED_FORMAT));
+        };
+
+        let mut tags = BTreeMap::new();
+        let mut origin = None;
+        let mut line_start = None;
+        let mut range = None;
+        let mut level = AnnotationType::default();
 
-        Annotation {
-            content: content_str.to_string(),
+        for part in parts.filter(|part| !part.is_empty()) {
+            if let Some(anchor) = part.strip_prefix("@anchor=") {
+                let fields: Vec<&str> = anchor.split('|').collect();
+
+                if let [path, start_str, start, end] = fields.as_slice() {
+                    if let (Ok(parsed_line_start), Ok(parsed_start), Ok(parsed_end)) =
+                        (start_str.parse(), start.parse(), end.parse())
+                    {
+                        origin = Some(PathBuf::from(path));
+                        line_start = Some(parsed_line_start);
+                        range = Some((parsed_start, parsed_end));
+                    }
+                }
+            } else if let Some(level_str) = part.strip_prefix("@level=") {
+                if let Ok(parsed_level) = level_str.parse() {
+                    level = parsed_level;
+                }
+            } else if let Some((key, value)) = part.split_once('=') {
+                tags.insert(key.to_string(), value.to_string());
+            }
+        }
+
+        Ok(Annotation {
+            content: unescape_line_content(content_str),
             created_at,
+            tags,
+            origin,
+            line_start,
+            range,
+            level,
+        })
+    }
+}
+
+impl Annotation {
+    /// Serializes this annotation's content with embedded backslashes, tabs and newlines escaped.
+    ///
+    /// The line format splits on `\t` to find the metadata section and on `\n` to find the next
+    /// annotation, so a literal tab or newline in `content` would otherwise be silently swallowed
+    /// into the metadata section or truncate the line. [`unescape_line_content`] reverses this on
+    /// read.
+    ///
+    /// # Returns
+    ///
+    /// A `String` containing the content, with `\`, `\t` and `\n` escaped.
+    pub fn serialize_content(&self) -> String {
+        escape_line_content(&self.content)
+    }
+
+    /// Serializes this annotation's tags as a `\t`-delimited `key=value` section.
+    ///
+    /// This is appended after the `<timestamp> <content>` pair when persisting an annotation, and
+    /// is empty when the annotation has no tags, so existing entries remain unaffected.
+    ///
+    /// # Returns
+    ///
+    /// A `String` containing the serialized tags, or an empty string if there are none.
+    pub fn serialize_tags(&self) -> String {
+        self.tags
+            .iter()
+            .map(|(key, value)| format!("\t{}={}", key, value))
+            .collect()
+    }
+
+    /// Serializes this annotation's source anchor, if any, as a `\t@anchor=...` token.
+    ///
+    /// The token encodes the `origin` path, `line_start` and `range` as `path|line_start|start|end`,
+    /// and is empty when the annotation has no anchor, so unanchored entries are unaffected.
+    ///
+    /// # Returns
+    ///
+    /// A `String` containing the serialized anchor, or an empty string if there is none.
+    pub fn serialize_anchor(&self) -> String {
+        match (self.origin.as_ref(), self.line_start, self.range) {
+            (Some(origin), Some(line_start), Some((start, end))) => format!(
+                "\t@anchor={}|{}|{}|{}",
+                origin.display(),
+                line_start,
+                start,
+                end
+            ),
+            _ => String::new(),
+        }
+    }
+
+    /// Serializes this annotation's severity as a `\t@level=...` token.
+    ///
+    /// The [`AnnotationType::Note`] level is the default, so it is omitted to keep existing
+    /// entries unaffected.
+    ///
+    /// # Returns
+    ///
+    /// A `String` containing the serialized level, or an empty string for [`AnnotationType::Note`].
+    pub fn serialize_level(&self) -> String {
+        if self.level == AnnotationType::Note {
+            String::new()
+        } else {
+            format!("\t@level={}", self.level)
         }
     }
+
+    /// Serializes this annotation's tags, source anchor and level for persistence.
+    ///
+    /// This is the combined `\t`-delimited section appended after the `<timestamp> <content>` pair
+    /// by [`crate::storage::LineStorage`] when it saves annotations.
+    ///
+    /// # Returns
+    ///
+    /// A `String` containing the serialized tags, anchor and level, in that order.
+    pub fn serialize_metadata(&self) -> String {
+        format!(
+            "{}{}{}",
+            self.serialize_tags(),
+            self.serialize_anchor(),
+            self.serialize_level()
+        )
+    }
 }
 
 impl Display for Annotation {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
-        write!(f, "({}, {})", self.created_at, self.content)
+        write!(f, "({}, {})", self.created_at, self.content)?;
+
+        if !self.tags.is_empty() {
+            let tags = self
+                .tags
+                .iter()
+                .map(|(key, value)| format!("{}={}", key, value))
+                .collect::<Vec<_>>()
+                .join(", ");
+
+            write!(f, " [{}]", tags)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trips_an_annotation_without_tags() {
+        let annotation = Annotation::new_with_metadata(
+            "fix the parser",
+            BTreeMap::new(),
+            None,
+            AnnotationType::default(),
+        );
+        let line = format!(
+            "{} {}{}",
+            annotation.created_at,
+            annotation.content,
+            annotation.serialize_tags()
+        );
+
+        let parsed = Annotation::try_from(line.as_str()).expect("line should parse");
+
+        assert_eq!(parsed.created_at, annotation.created_at);
+        assert_eq!(parsed.content, annotation.content);
+        assert!(parsed.tags.is_empty());
+    }
+
+    #[test]
+    fn round_trips_an_annotation_with_tags() {
+        let mut tags = BTreeMap::new();
+        tags.insert("project".to_string(), "jotspot".to_string());
+        tags.insert("priority".to_string(), "high".to_string());
+
+        let annotation =
+            Annotation::new_with_metadata("fix the parser", tags, None, AnnotationType::default());
+        let line = format!(
+            "{} {}{}",
+            annotation.created_at,
+            annotation.content,
+            annotation.serialize_tags()
+        );
+
+        let parsed = Annotation::try_from(line.as_str()).expect("line should parse");
+
+        assert_eq!(parsed.created_at, annotation.created_at);
+        assert_eq!(parsed.content, annotation.content);
+        assert_eq!(parsed.tags, annotation.tags);
+    }
+
+    #[test]
+    fn round_trips_an_annotation_whose_content_has_a_literal_tab() {
+        let mut tags = BTreeMap::new();
+        tags.insert("project".to_string(), "jotspot".to_string());
+
+        let annotation = Annotation::new_with_metadata(
+            "line1\tline2",
+            tags,
+            None,
+            AnnotationType::default(),
+        );
+        let line = format!(
+            "{} {}{}",
+            annotation.created_at,
+            annotation.serialize_content(),
+            annotation.serialize_tags()
+        );
+
+        let parsed = Annotation::try_from(line.as_str()).expect("line should parse");
+
+        assert_eq!(parsed.created_at, annotation.created_at);
+        assert_eq!(parsed.content, annotation.content);
+        assert_eq!(parsed.tags, annotation.tags);
+    }
+
+    #[test]
+    fn reports_a_parse_error_instead_of_panicking_on_a_missing_delimiter() {
+        let error = Annotation::try_from("not-a-valid-line")
+            .expect_err("line without a space delimiter should fail to parse");
+
+        assert_eq!(error.range, (0, "not-a-valid-line".len()));
+        assert_eq!(error.message, "expected `<timestamp> <content>`");
+    }
+
+    #[test]
+    fn reports_a_parse_error_instead_of_panicking_on_an_unparseable_timestamp() {
+        let error = Annotation::try_from("not-a-timestamp some content")
+            .expect_err("line with an unparseable timestamp should fail to parse");
+
+        assert_eq!(error.range, (0, "not-a-timestamp".len()));
+        assert_eq!(error.message, "expected `<timestamp> <content>`");
     }
 }
@@ -0,0 +1,438 @@
+use crate::annotation::{Annotation, AnnotationType};
+
+use std::path::PathBuf;
+
+/// A minimal JSON value, covering just the null/bool/number/string/array/object shapes that
+/// [`serialize_annotations`] and [`parse_annotations`] need to round-trip an [`Annotation`] array.
+#[derive(Debug, Clone)]
+enum JsonValue {
+    Null,
+    Bool(bool),
+    Number(i64),
+    String(String),
+    Array(Vec<JsonValue>),
+    Object(Vec<(String, JsonValue)>),
+}
+
+impl JsonValue {
+    fn as_str(&self) -> Option<&str> {
+        match self {
+            JsonValue::String(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    fn as_i64(&self) -> Option<i64> {
+        match self {
+            JsonValue::Number(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    fn as_array(&self) -> Option<&[JsonValue]> {
+        match self {
+            JsonValue::Array(items) => Some(items),
+            _ => None,
+        }
+    }
+
+    fn as_object(&self) -> Option<&[(String, JsonValue)]> {
+        match self {
+            JsonValue::Object(entries) => Some(entries),
+            _ => None,
+        }
+    }
+
+    fn get(&self, key: &str) -> Option<&JsonValue> {
+        self.as_object()?
+            .iter()
+            .find(|(entry_key, _)| entry_key == key)
+            .map(|(_, value)| value)
+    }
+}
+
+/// A cursor-based recursive-descent parser over a fixed `Vec<char>` buffer.
+struct JsonParser {
+    chars: Vec<char>,
+    pos: usize,
+}
+
+impl JsonParser {
+    fn new(input: &str) -> Self {
+        Self {
+            chars: input.chars().collect(),
+            pos: 0,
+        }
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let next = self.peek();
+        if next.is_some() {
+            self.pos += 1;
+        }
+        next
+    }
+
+    fn consume_literal(&mut self, literal: &str) -> bool {
+        let literal_chars: Vec<char> = literal.chars().collect();
+
+        if self.chars[self.pos..].starts_with(literal_chars.as_slice()) {
+            self.pos += literal_chars.len();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn skip_whitespace(&mut self) {
+        while matches!(self.peek(), Some(c) if c.is_whitespace()) {
+            self.pos += 1;
+        }
+    }
+
+    fn parse_value(&mut self) -> Result<JsonValue, String> {
+        self.skip_whitespace();
+
+        match self.peek() {
+            Some('{') => self.parse_object(),
+            Some('[') => self.parse_array(),
+            Some('"') => self.parse_string().map(JsonValue::String),
+            Some('t') | Some('f') => self.parse_bool(),
+            Some('n') => self.parse_null(),
+            Some(c) if c == '-' || c.is_ascii_digit() => self.parse_number(),
+            _ => Err("expected a JSON value".to_string()),
+        }
+    }
+
+    fn parse_object(&mut self) -> Result<JsonValue, String> {
+        self.bump();
+        self.skip_whitespace();
+
+        let mut entries = Vec::new();
+
+        if self.peek() == Some('}') {
+            self.bump();
+            return Ok(JsonValue::Object(entries));
+        }
+
+        loop {
+            self.skip_whitespace();
+            let key = self.parse_string()?;
+            self.skip_whitespace();
+
+            if self.bump() != Some(':') {
+                return Err("expected ':' after object key".to_string());
+            }
+
+            let value = self.parse_value()?;
+            entries.push((key, value));
+            self.skip_whitespace();
+
+            match self.bump() {
+                Some(',') => continue,
+                Some('}') => break,
+                _ => return Err("expected ',' or '}' in object".to_string()),
+            }
+        }
+
+        Ok(JsonValue::Object(entries))
+    }
+
+    fn parse_array(&mut self) -> Result<JsonValue, String> {
+        self.bump();
+        self.skip_whitespace();
+
+        let mut items = Vec::new();
+
+        if self.peek() == Some(']') {
+            self.bump();
+            return Ok(JsonValue::Array(items));
+        }
+
+        loop {
+            items.push(self.parse_value()?);
+            self.skip_whitespace();
+
+            match self.bump() {
+                Some(',') => continue,
+                Some(']') => break,
+                _ => return Err("expected ',' or ']' in array".to_string()),
+            }
+        }
+
+        Ok(JsonValue::Array(items))
+    }
+
+    fn parse_string(&mut self) -> Result<String, String> {
+        if self.bump() != Some('"') {
+            return Err("expected '\"'".to_string());
+        }
+
+        let mut result = String::new();
+
+        loop {
+            match self.bump() {
+                Some('"') => break,
+                Some('\\') => match self.bump() {
+                    Some('"') => result.push('"'),
+                    Some('\\') => result.push('\\'),
+                    Some('/') => result.push('/'),
+                    Some('n') => result.push('\n'),
+                    Some('t') => result.push('\t'),
+                    Some('r') => result.push('\r'),
+                    Some(other) => result.push(other),
+                    None => return Err("unterminated escape in string".to_string()),
+                },
+                Some(c) => result.push(c),
+                None => return Err("unterminated string".to_string()),
+            }
+        }
+
+        Ok(result)
+    }
+
+    fn parse_bool(&mut self) -> Result<JsonValue, String> {
+        if self.consume_literal("true") {
+            Ok(JsonValue::Bool(true))
+        } else if self.consume_literal("false") {
+            Ok(JsonValue::Bool(false))
+        } else {
+            Err("expected 'true' or 'false'".to_string())
+        }
+    }
+
+    fn parse_null(&mut self) -> Result<JsonValue, String> {
+        if self.consume_literal("null") {
+            Ok(JsonValue::Null)
+        } else {
+            Err("expected 'null'".to_string())
+        }
+    }
+
+    fn parse_number(&mut self) -> Result<JsonValue, String> {
+        let start = self.pos;
+
+        if self.peek() == Some('-') {
+            self.pos += 1;
+        }
+
+        while matches!(self.peek(), Some(c) if c.is_ascii_digit()) {
+            self.pos += 1;
+        }
+
+        let text: String = self.chars[start..self.pos].iter().collect();
+        text.parse::<i64>()
+            .map(JsonValue::Number)
+            .map_err(|_| "invalid number".to_string())
+    }
+}
+
+/// Escapes `value` for embedding as a JSON string literal.
+fn escape_json_string(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+
+    for c in value.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\t' => escaped.push_str("\\t"),
+            '\r' => escaped.push_str("\\r"),
+            c => escaped.push(c),
+        }
+    }
+
+    escaped
+}
+
+fn serialize_annotation(annotation: &Annotation) -> String {
+    let tags = annotation
+        .tags
+        .iter()
+        .map(|(key, value)| {
+            format!(
+                "\"{}\":\"{}\"",
+                escape_json_string(key),
+                escape_json_string(value)
+            )
+        })
+        .collect::<Vec<_>>()
+        .join(",");
+
+    let origin = match &annotation.origin {
+        Some(origin) => format!("\"{}\"", escape_json_string(&origin.display().to_string())),
+        None => "null".to_string(),
+    };
+
+    let line_start = annotation
+        .line_start
+        .map(|line_start| line_start.to_string())
+        .unwrap_or_else(|| "null".to_string());
+
+    let range = match annotation.range {
+        Some((start, end)) => format!("[{},{}]", start, end),
+        None => "null".to_string(),
+    };
+
+    format!(
+        "{{\"created_at\":{},\"content\":\"{}\",\"tags\":{{{}}},\"origin\":{},\"line_start\":{},\"range\":{},\"level\":\"{}\"}}",
+        annotation.created_at,
+        escape_json_string(&annotation.content),
+        tags,
+        origin,
+        line_start,
+        range,
+        annotation.level
+    )
+}
+
+/// Serializes `annotations` as a JSON array, one object per annotation.
+///
+/// Every field of [`Annotation`] is carried across, so this format has no need for the
+/// `.annotations` line format's `\t`-delimited metadata tokens.
+pub fn serialize_annotations(annotations: &[Annotation]) -> String {
+    let items = annotations
+        .iter()
+        .map(serialize_annotation)
+        .collect::<Vec<_>>()
+        .join(",");
+
+    format!("[{}]\n", items)
+}
+
+fn annotation_from_json(value: &JsonValue) -> Option<Annotation> {
+    let created_at = value.get("created_at")?.as_i64()? as u64;
+    let content = value.get("content")?.as_str()?.to_string();
+
+    let tags = value
+        .get("tags")?
+        .as_object()?
+        .iter()
+        .filter_map(|(key, value)| Some((key.clone(), value.as_str()?.to_string())))
+        .collect();
+
+    let origin = value
+        .get("origin")
+        .and_then(JsonValue::as_str)
+        .map(PathBuf::from);
+
+    let line_start = value
+        .get("line_start")
+        .and_then(JsonValue::as_i64)
+        .map(|line_start| line_start as usize);
+
+    let range = value
+        .get("range")
+        .and_then(JsonValue::as_array)
+        .and_then(|items| match items {
+            [start, end] => Some((start.as_i64()? as usize, end.as_i64()? as usize)),
+            _ => None,
+        });
+
+    let level = value
+        .get("level")
+        .and_then(JsonValue::as_str)
+        .and_then(|level| level.parse().ok())
+        .unwrap_or(AnnotationType::default());
+
+    Some(Annotation {
+        content,
+        created_at,
+        tags,
+        origin,
+        line_start,
+        range,
+        level,
+    })
+}
+
+/// Parses a JSON array of serialized annotations, as produced by [`serialize_annotations`].
+///
+/// # Errors
+///
+/// Returns a human-readable message describing the first malformed token encountered, instead of
+/// panicking on a damaged `.annotations.json` file.
+pub fn parse_annotations(source: &str) -> Result<Vec<Annotation>, String> {
+    let value = JsonParser::new(source).parse_value()?;
+    let items = value.as_array().ok_or("expected a top-level JSON array")?;
+
+    items
+        .iter()
+        .map(|item| annotation_from_json(item).ok_or_else(|| "malformed annotation object".to_string()))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn round_trips_an_annotation_through_serialize_and_parse() {
+        let mut tags = BTreeMap::new();
+        tags.insert("project".to_string(), "jotspot".to_string());
+
+        let annotation = Annotation {
+            content: "fix the parser\twith a \"quote\" and a newline\n".to_string(),
+            created_at: 1_700_000_000_000,
+            tags,
+            origin: Some(PathBuf::from("src/main.rs")),
+            line_start: Some(12),
+            range: Some((4, 9)),
+            level: AnnotationType::Warning,
+        };
+
+        let json = serialize_annotations(std::slice::from_ref(&annotation));
+        let parsed = parse_annotations(&json).expect("serialized annotations should parse");
+
+        assert_eq!(parsed.len(), 1);
+        assert_eq!(parsed[0].content, annotation.content);
+        assert_eq!(parsed[0].created_at, annotation.created_at);
+        assert_eq!(parsed[0].tags, annotation.tags);
+        assert_eq!(parsed[0].origin, annotation.origin);
+        assert_eq!(parsed[0].line_start, annotation.line_start);
+        assert_eq!(parsed[0].range, annotation.range);
+        assert_eq!(parsed[0].level, annotation.level);
+    }
+
+    #[test]
+    fn parses_an_empty_array() {
+        let parsed = parse_annotations("[]\n").expect("an empty array should parse");
+        assert!(parsed.is_empty());
+    }
+
+    #[test]
+    fn rejects_truncated_json() {
+        let error = parse_annotations("[{\"created_at\":1,\"content\":\"note\"")
+            .expect_err("truncated JSON should not parse");
+
+        assert!(!error.is_empty());
+    }
+
+    #[test]
+    fn rejects_garbage_input() {
+        let error = parse_annotations("not json at all").expect_err("garbage input should not parse");
+        assert!(!error.is_empty());
+    }
+
+    #[test]
+    fn rejects_a_non_array_top_level_value() {
+        let error =
+            parse_annotations("{\"created_at\":1}").expect_err("a bare object is not a valid top level");
+
+        assert_eq!(error, "expected a top-level JSON array");
+    }
+
+    #[test]
+    fn rejects_an_annotation_missing_a_required_field() {
+        let error = parse_annotations("[{\"content\":\"missing created_at\"}]")
+            .expect_err("an annotation missing created_at should not parse");
+
+        assert_eq!(error, "malformed annotation object");
+    }
+}
@@ -1,8 +1,16 @@
 mod annotation;
+mod json;
 mod metadata;
+mod snippet;
+mod storage;
 mod ui;
 
+use annotation::AnnotationType;
+
+use std::collections::BTreeMap;
 use std::env;
+use std::fs;
+use std::path::PathBuf;
 
 fn main() {
     let args: Vec<String> = env::args().skip(1).collect();
@@ -12,7 +20,187 @@ fn main() {
             Ok(annotations) => ui::run_annotate_tui(annotations),
             Err(e) => eprintln!("Couldn't read annotations: {}", e),
         }
+    } else if args[0] == "--get" {
+        run_get(&args[1..]);
+    } else if args[0] == "--get-all" {
+        run_get_all(&args[1..]);
+    } else if args[0] == "show" {
+        run_show(&args[1..]);
     } else {
-        metadata::annotate(&args.join(" "));
+        run_annotate(&args);
+    }
+}
+
+/// Parses `--tag key=value` options out of `args`, returning the remaining arguments alongside the
+/// collected tags.
+///
+/// # Arguments
+///
+/// - `args`: The command-line arguments following the program name.
+///
+/// # Returns
+///
+/// A tuple of the parsed `tags` and the leftover `content` arguments, in their original order.
+fn parse_tags(args: &[String]) -> (BTreeMap<String, String>, Vec<String>) {
+    let mut tags = BTreeMap::new();
+    let mut content = Vec::new();
+
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        if arg == "--tag" {
+            if let Some(tag) = iter.next() {
+                if let Some((key, value)) = tag.split_once('=') {
+                    tags.insert(key.to_string(), value.to_string());
+                }
+            }
+        } else {
+            content.push(arg.clone());
+        }
+    }
+
+    (tags, content)
+}
+
+/// Parses a single `--at file:line_start:start:end` option out of `args`, returning the remaining
+/// arguments alongside the parsed anchor, if any.
+///
+/// # Arguments
+///
+/// - `args`: The command-line arguments following the program name.
+///
+/// # Returns
+///
+/// A tuple of the parsed anchor and the leftover arguments, in their original order.
+fn parse_anchor(args: &[String]) -> (Option<(PathBuf, usize, (usize, usize))>, Vec<String>) {
+    let mut anchor = None;
+    let mut rest = Vec::new();
+
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        if arg == "--at" {
+            if let Some(spec) = iter.next() {
+                let fields: Vec<&str> = spec.split(':').collect();
+
+                if let [path, line_start, start, end] = fields.as_slice() {
+                    if let (Ok(line_start), Ok(start), Ok(end)) =
+                        (line_start.parse::<usize>(), start.parse(), end.parse())
+                    {
+                        if line_start >= 1 {
+                            anchor = Some((PathBuf::from(path), line_start, (start, end)));
+                        } else {
+                            eprintln!("--at line_start must be >= 1 (lines are 1-based)");
+                        }
+                    }
+                }
+            }
+        } else {
+            rest.push(arg.clone());
+        }
+    }
+
+    (anchor, rest)
+}
+
+/// Parses a single `--level <level>` option out of `args`, returning the remaining arguments
+/// alongside the parsed level, if any.
+///
+/// # Arguments
+///
+/// - `args`: The command-line arguments following the program name.
+///
+/// # Returns
+///
+/// A tuple of the parsed level and the leftover arguments, in their original order.
+fn parse_level(args: &[String]) -> (Option<AnnotationType>, Vec<String>) {
+    let mut level = None;
+    let mut rest = Vec::new();
+
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        if arg == "--level" {
+            if let Some(level_str) = iter.next() {
+                match level_str.parse() {
+                    Ok(parsed_level) => level = Some(parsed_level),
+                    Err(e) => eprintln!("{}", e),
+                }
+            }
+        } else {
+            rest.push(arg.clone());
+        }
+    }
+
+    (level, rest)
+}
+
+/// Handles `jotspot [--tag key=value ...] [--at file:line_start:start:end] [--level <level>]
+/// <content>`, writing a new annotation to the metadata file.
+///
+/// Tags, an anchor and a level can all be given at once: they're composed onto a single
+/// annotation via [`metadata::annotate_with_metadata`] rather than picking just one.
+fn run_annotate(args: &[String]) {
+    let (anchor, args) = parse_anchor(args);
+    let (level, args) = parse_level(&args);
+    let (tags, content) = parse_tags(&args);
+    let content = content.join(" ");
+
+    let result =
+        metadata::annotate_with_metadata(&content, tags, anchor, level.unwrap_or_default());
+
+    if let Err(e) = result {
+        eprintln!("Couldn't write annotation: {}", e);
+    }
+}
+
+/// Handles `jotspot --get <key>`, printing the value of `key` on the most recent annotation that
+/// carries it.
+fn run_get(args: &[String]) {
+    let Some(key) = args.first() else {
+        eprintln!("Usage: jotspot --get <key>");
+        return;
+    };
+
+    match metadata::get_tag(key) {
+        Ok(Some(value)) => println!("{}", value),
+        Ok(None) => eprintln!("No annotation has the '{}' tag set.", key),
+        Err(e) => eprintln!("Couldn't read annotations: {}", e),
+    }
+}
+
+/// Handles `jotspot --get-all <key>`, printing every annotation that carries the `key` tag.
+fn run_get_all(args: &[String]) {
+    let Some(key) = args.first() else {
+        eprintln!("Usage: jotspot --get-all <key>");
+        return;
+    };
+
+    match metadata::get_tag_all(key) {
+        Ok(annotations) => {
+            for annotation in annotations {
+                println!("{}", annotation);
+            }
+        }
+        Err(e) => eprintln!("Couldn't read annotations: {}", e),
+    }
+}
+
+/// Handles `jotspot show <file>`, rendering every annotation anchored to `file` alongside its
+/// source.
+fn run_show(args: &[String]) {
+    let Some(path) = args.first().map(PathBuf::from) else {
+        eprintln!("Usage: jotspot show <file>");
+        return;
+    };
+
+    let source = match fs::read_to_string(&path) {
+        Ok(source) => source,
+        Err(e) => {
+            eprintln!("Couldn't read {}: {}", path.display(), e);
+            return;
+        }
+    };
+
+    match metadata::get_annotations_for_origin(&path) {
+        Ok(annotations) => print!("{}", snippet::render_snippet(&path, &source, &annotations)),
+        Err(e) => eprintln!("Couldn't read annotations: {}", e),
     }
 }
@@ -1,14 +1,31 @@
-use crate::annotation::{Annotation, AnnotationsData};
+use crate::annotation::{Annotation, AnnotationType, AnnotationsData};
+use crate::snippet;
+use crate::storage::{self, Storage};
 
+use std::collections::BTreeMap;
 use std::env;
-use std::fs::OpenOptions;
-use std::io::{self, Read, Write};
-use std::path::PathBuf;
+use std::io;
+use std::path::{Path, PathBuf};
+
+fn home_dir() -> PathBuf {
+    let homedir_path = env::var("HOME").expect("Failed to get the home directory");
+    PathBuf::from(homedir_path)
+}
+
+/// The path of the legacy line-oriented annotations file, regardless of which format is active.
+fn legacy_annotations_filename() -> PathBuf {
+    home_dir().join(".annotations")
+}
+
+/// The path of the structured JSON annotations file, regardless of which format is active.
+fn json_annotations_filename() -> PathBuf {
+    home_dir().join(".annotations.json")
+}
 
 /// Get the annotations metadata file path.
 ///
-/// This function retrieves the home directory path of the current user and appends the
-/// ".annotations" file name to it, forming the complete file path for storing annotations.
+/// This function retrieves the home directory path of the current user and, by default, appends
+/// the ".annotations" file name to it, forming the complete file path for storing annotations.
 ///
 /// # Returns
 ///
@@ -29,77 +46,93 @@ use std::path::PathBuf;
 ///
 /// # Note
 ///
-/// - This function is designed to provide a standardized file path for the annotations file,
-///   assuming that it should be stored in the user's home directory with the filename ".annotations".
+/// - If a `.annotations.json` file already exists, or the `JOTSPOT_FORMAT` environment variable is
+///   set to `json`, this returns the `.annotations.json` path instead, so that opting into the
+///   structured JSON format (see [`crate::storage`]) sticks once a file has been migrated to it.
 pub fn get_annotations_filename() -> PathBuf {
-    let homedir_path = env::var("HOME").expect("Failed to get the home directory");
-    PathBuf::from(homedir_path).join(".annotations")
+    let json_path = json_annotations_filename();
+
+    if json_path.exists() || env::var("JOTSPOT_FORMAT").as_deref() == Ok("json") {
+        json_path
+    } else {
+        legacy_annotations_filename()
+    }
 }
 
-/// Appends a new annotation to the metadata file with a timestamp and content.
+/// Migrates a legacy `.annotations` file to the structured JSON format, if requested.
+///
+/// This is checked before every read in [`read_annotations`] and every append in
+/// [`write_annotation`], since reading the JSON file creates it and would otherwise mask the
+/// legacy file from later checks. If `JOTSPOT_FORMAT=json` is set, no `.annotations.json`
+/// file exists yet, and a legacy `.annotations` file does exist, its annotations are carried over
+/// into a freshly written `.annotations.json` before the write proceeds. This way, opting into the
+/// JSON format doesn't silently discard annotations recorded under the line format.
+fn migrate_legacy_format_if_requested() -> io::Result<()> {
+    if env::var("JOTSPOT_FORMAT").as_deref() != Ok("json") {
+        return Ok(());
+    }
+
+    let json_path = json_annotations_filename();
+    let legacy_path = legacy_annotations_filename();
+
+    if json_path.exists() || !legacy_path.exists() {
+        return Ok(());
+    }
+
+    let (annotations, _errors, _source) = storage::LineStorage.load(&legacy_path)?;
+    storage::JsonStorage.save(&json_path, &AnnotationsData::new(annotations))
+}
+
+/// Appends a new [`Annotation`] to the metadata file, migrating to the JSON format first if
+/// [`migrate_legacy_format_if_requested`] determines it's needed.
+fn write_annotation(annotation: Annotation) -> io::Result<()> {
+    migrate_legacy_format_if_requested()?;
+
+    let path = get_annotations_filename();
+    let storage = storage::storage_for(&path);
+    let (mut annotations, _errors, _source) = storage.load(&path)?;
+
+    annotations.push(annotation);
+    storage.save(&path, &AnnotationsData::new(annotations))
+}
+
+/// Appends a new annotation to the metadata file, combining tags, an optional source anchor, and
+/// a severity level on the same entry.
 ///
-/// This function is used to add a new annotation to the metadata, including a timestamp indicating when
-/// the annotation was created and the textual content of the annotation. The annotations are stored
-/// in a specific format where each line represents an annotation entry.
+/// This backs `jotspot`'s CLI, which accepts `--tag`, `--at` and `--level` together rather than
+/// forcing a choice between them: every combination is persisted on a single
+/// [`Annotation`] via [`Annotation::new_with_metadata`], instead of dropping whichever flags
+/// aren't picked by a single-purpose constructor.
 ///
 /// # Arguments
 ///
 /// - `content`: A `String` containing the textual content of the annotation to be added.
-///
-/// # Examples
-///
-/// ```rust
-/// let content = "This is a new annotation.";
-/// annotate(&content);
-/// ```
-///
-/// # Note
-///
-/// - This function is designed to add new annotations to the metadata file in a specific format, where each line
-///   represents an annotation entry. The format is as follows:
-///
-///   ```text
-///   <TIMESTAMP> <CONTENT>
-///   ```
-///
-///   where:
-///
-///   - `<TIMESTAMP>` is the timestamp in milliseconds indicating when the annotation was created.
-///   - `<CONTENT>` is the textual content of the annotation.
-///
-/// - The `annotate` function appends the new annotation to the file, ensuring that it adheres to the
-///   specified format.
-///
-/// - If the operation fails (e.g., due to file I/O issues), an error message is printed to the
-///   standard error stream.
-pub fn annotate(content: &str) -> io::Result<()> {
-    let mut file = OpenOptions::new()
-        .create(true)
-        .append(true)
-        .open(get_annotations_filename())?;
-
-    let annotation = Annotation::new(content);
-
-    writeln!(file, "{} {}", annotation.created_at, annotation.content)?;
-
-    Ok(())
+/// - `tags`: The `key=value` tags to attach to the annotation.
+/// - `anchor`: The `(origin, line_start, range)` source location to anchor the annotation to, if
+///   any.
+/// - `level`: The severity of the annotation.
+pub fn annotate_with_metadata(
+    content: &str,
+    tags: BTreeMap<String, String>,
+    anchor: Option<(PathBuf, usize, (usize, usize))>,
+    level: AnnotationType,
+) -> io::Result<()> {
+    write_annotation(Annotation::new_with_metadata(content, tags, anchor, level))
 }
 
 /// Reads and parses annotations from the metadata file into a vector of `Annotation` instances.
 ///
-/// This function is responsible for reading and parsing annotations from a file and converting
-/// them into a vector of `Annotation` instances. Annotations in the file should be stored in a
-/// specific format, with each line representing an annotation entry containing a timestamp and
-/// content.
-///
-/// # Returns
+/// This function reads and parses annotations from the metadata file, using whichever
+/// [`Storage`] implementation matches its format (see [`storage::storage_for`]).
 ///
-/// - A `Vec<Annotation>` containing parsed annotations.
+/// Malformed entries no longer abort the whole read: each one is collected as a [`ParseError`]
+/// instead of being parsed, and once the file has been read, any collected errors are rendered
+/// as a caret-annotated snippet and printed to stderr. The annotations that did parse are still
+/// returned, so a damaged notes file degrades gracefully instead of taking down the TUI.
 ///
-/// # Panics
+/// # Returns
 ///
-/// - If the annotations file cannot be read or if any annotation entry is in an invalid format,
-///   the function will panic with an error message.
+/// - A `Vec<Annotation>` containing every annotation that parsed successfully.
 ///
 /// # Examples
 ///
@@ -110,37 +143,16 @@ pub fn annotate(content: &str) -> io::Result<()> {
 /// }
 /// ```
 ///
-/// # Note
-///
-/// - This function is designed to read and parse annotations from a file where each line follows
-///   the specified format:
-///
-///   ```text
-///   <TIMESTAMP> <CONTENT>
-///   ```
-///
-///   where:
-///
-///   - `<TIMESTAMP>` is the timestamp in milliseconds indicating when the annotation was created.
-///   - `<CONTENT>` is the textual content of the annotation.
-///
-/// - If the annotations file is not found, empty, or contains entries in an invalid format, the function
-///   will panic with an error message.
+/// [`ParseError`]: crate::annotation::ParseError
 pub fn read_annotations() -> io::Result<Vec<Annotation>> {
-    let mut lines = String::from("");
+    migrate_legacy_format_if_requested()?;
 
-    OpenOptions::new()
-        .create(true)
-        .write(true)
-        .read(true)
-        .open(get_annotations_filename())?
-        .read_to_string(&mut lines)?;
+    let path = get_annotations_filename();
+    let (annotations, errors, source) = storage::storage_for(&path).load(&path)?;
 
-    let annotations = lines
-        .lines()
-        .filter(|line| !line.is_empty())
-        .map(|line| Annotation::from(line))
-        .collect();
+    if !errors.is_empty() {
+        eprint!("{}", snippet::render_parse_errors(&path, &source, &errors));
+    }
 
     Ok(annotations)
 }
@@ -155,18 +167,74 @@ pub fn read_annotations() -> io::Result<Vec<Annotation>> {
 ///
 /// This function may panic if it encounters errors while opening or writing to the file.
 pub fn save_annotations(data: &AnnotationsData) {
-    let mut file = OpenOptions::new()
-        .create(true)
-        .truncate(true)
-        .write(true)
-        .open(get_annotations_filename())
-        .expect("Couldn't open the file");
+    let path = get_annotations_filename();
 
-    let mut content = String::new();
+    storage::storage_for(&path)
+        .save(&path, data)
+        .expect("Couldn't save annotations");
+}
 
-    for annotation in data.get_annotations() {
-        content.push_str(format!("{} {}\n", annotation.created_at, annotation.content).as_str());
-    }
+/// Finds the value of `key` on the most recently created annotation that has it set.
+///
+/// This backs `jotspot --get <key>`.
+///
+/// # Arguments
+///
+/// - `key`: The tag key to look up.
+///
+/// # Returns
+///
+/// - `Ok(Some(value))` if some annotation carries a tag named `key`, taking the most recently
+///   created match when several annotations carry it.
+/// - `Ok(None)` if no annotation carries the tag.
+pub fn get_tag(key: &str) -> io::Result<Option<String>> {
+    let annotations = read_annotations()?;
+
+    Ok(annotations
+        .iter()
+        .filter(|annotation| annotation.tags.contains_key(key))
+        .max_by_key(|annotation| annotation.created_at)
+        .and_then(|annotation| annotation.tags.get(key).cloned()))
+}
+
+/// Finds every annotation that has the tag `key` set.
+///
+/// This backs `jotspot --get-all <key>`.
+///
+/// # Arguments
+///
+/// - `key`: The tag key to match.
+///
+/// # Returns
+///
+/// A `Vec<Annotation>` containing every annotation carrying the tag, in the order they were
+/// stored.
+pub fn get_tag_all(key: &str) -> io::Result<Vec<Annotation>> {
+    let annotations = read_annotations()?;
+
+    Ok(annotations
+        .into_iter()
+        .filter(|annotation| annotation.tags.contains_key(key))
+        .collect())
+}
+
+/// Finds every annotation anchored to `origin`.
+///
+/// This backs `jotspot show <file>`.
+///
+/// # Arguments
+///
+/// - `origin`: The source file path to match against each annotation's `origin`.
+///
+/// # Returns
+///
+/// A `Vec<Annotation>` containing every annotation anchored to `origin`, in the order they were
+/// stored.
+pub fn get_annotations_for_origin(origin: &Path) -> io::Result<Vec<Annotation>> {
+    let annotations = read_annotations()?;
 
-    writeln!(file, "{}", content).expect("Couldn't write to the file");
+    Ok(annotations
+        .into_iter()
+        .filter(|annotation| annotation.origin.as_deref() == Some(origin))
+        .collect())
 }
@@ -0,0 +1,262 @@
+use crate::annotation::{Annotation, ParseError};
+
+use std::path::Path;
+
+/// A single caret-underlined range to render beneath one line of source, by [`render`].
+struct SourceAnnotation<'a> {
+    /// The 1-based line number this annotation points at.
+    line_start: usize,
+
+    /// The `(start, end)` char range within the line to underline.
+    range: (usize, usize),
+
+    /// The text printed beneath the underlined range.
+    label: &'a str,
+}
+
+/// Renders `source` as line-numbered text with caret underlines beneath each annotated range.
+///
+/// Untouched lines between distant annotations are folded into a single `...` marker instead of
+/// being printed in full. An optional `footer` is appended after the last annotation.
+///
+/// # Arguments
+///
+/// - `header`: The header line printed before the source, such as a file path.
+/// - `source`: The raw text being annotated.
+/// - `source_annotations`: The annotations to render, one per highlighted range.
+/// - `footer`: An optional note appended after the rendered source.
+///
+/// # Returns
+///
+/// A `String` containing the rendered, line-numbered block.
+fn render(
+    header: &str,
+    source: &str,
+    mut source_annotations: Vec<SourceAnnotation>,
+    footer: Option<&str>,
+) -> String {
+    source_annotations.sort_by_key(|annotation| annotation.line_start);
+
+    let lines: Vec<&str> = source.lines().collect();
+    let gutter_width = lines.len().to_string().len();
+
+    let mut output = format!("{}:\n", header);
+    let mut previous_line: Option<usize> = None;
+
+    for source_annotation in &source_annotations {
+        let line_number = source_annotation.line_start;
+
+        match previous_line {
+            Some(prev) if line_number == prev + 1 => {}
+            Some(prev) if line_number > prev => {
+                output.push_str(&format!("{:>width$} | ...\n", "", width = gutter_width));
+            }
+            _ => {}
+        }
+
+        let line_text = line_number
+            .checked_sub(1)
+            .and_then(|index| lines.get(index))
+            .copied()
+            .unwrap_or("");
+        output.push_str(&format!(
+            "{:>width$} | {}\n",
+            line_number,
+            line_text,
+            width = gutter_width
+        ));
+
+        let (start, end) = source_annotation.range;
+        let carets = "^".repeat(end.saturating_sub(start).max(1));
+        output.push_str(&format!(
+            "{:>width$} | {}{} {}\n",
+            "",
+            " ".repeat(start),
+            carets,
+            source_annotation.label,
+            width = gutter_width
+        ));
+
+        previous_line = Some(line_number);
+    }
+
+    if let Some(footer) = footer {
+        output.push_str(&format!("{:>width$} = {}\n", "", footer, width = gutter_width));
+    }
+
+    output
+}
+
+/// Renders every annotation anchored to `origin` as line-numbered source with caret underlines.
+///
+/// This backs `jotspot show <file>`. For each line referenced by an annotation, the matched source
+/// line is printed with a gutter, followed by a row of `^^^` carets under the annotated range and
+/// the annotation's content as a label.
+///
+/// # Arguments
+///
+/// - `origin`: The path of the file being rendered, used only for the header line.
+/// - `source`: The raw contents of `origin`.
+/// - `annotations`: The annotations anchored to `origin`.
+///
+/// # Returns
+///
+/// A `String` containing the rendered snippet, or a message noting that there are no annotations
+/// to show.
+pub fn render_snippet(origin: &Path, source: &str, annotations: &[Annotation]) -> String {
+    let source_annotations: Vec<SourceAnnotation> = annotations
+        .iter()
+        .filter_map(|annotation| {
+            Some(SourceAnnotation {
+                line_start: annotation.line_start?,
+                range: annotation.range?,
+                label: annotation.content.as_str(),
+            })
+        })
+        .collect();
+
+    if source_annotations.is_empty() {
+        return format!("{}: no annotations anchored to this file.", origin.display());
+    }
+
+    render(&origin.display().to_string(), source, source_annotations, None)
+}
+
+/// Converts a byte `range` within `line` to the equivalent char range.
+///
+/// [`ParseError::range`] is a byte range (it's built from `str::len()` on substrings), but
+/// [`render`] lays out carets by char count, so a line containing multibyte characters before the
+/// offending token would otherwise render carets in the wrong column.
+fn byte_range_to_char_range(line: &str, range: (usize, usize)) -> (usize, usize) {
+    let char_offset =
+        |byte_offset: usize| line.char_indices().take_while(|(index, _)| *index < byte_offset).count();
+
+    (char_offset(range.0), char_offset(range.1))
+}
+
+/// Renders malformed `.annotations` lines as line-numbered source with caret underlines.
+///
+/// This lets a damaged notes file describe itself instead of aborting: each [`ParseError`] is
+/// rendered against the raw file contents it came from, with a footer note pointing at the
+/// expected format.
+///
+/// # Arguments
+///
+/// - `origin`: The path of the annotations file being rendered.
+/// - `source`: The raw contents of the annotations file.
+/// - `errors`: The parse errors collected while reading `source`.
+///
+/// # Returns
+///
+/// A `String` containing the rendered diagnostics.
+pub fn render_parse_errors(origin: &Path, source: &str, errors: &[ParseError]) -> String {
+    let lines: Vec<&str> = source.lines().collect();
+
+    let source_annotations: Vec<SourceAnnotation> = errors
+        .iter()
+        .map(|error| {
+            let line_text = error
+                .line
+                .checked_sub(1)
+                .and_then(|index| lines.get(index))
+                .copied()
+                .unwrap_or("");
+
+            SourceAnnotation {
+                line_start: error.line,
+                range: byte_range_to_char_range(line_text, error.range),
+                label: error.message.as_str(),
+            }
+        })
+        .collect();
+
+    render(
+        &origin.display().to_string(),
+        source,
+        source_annotations,
+        Some("malformed lines were skipped; see the carets above"),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::annotation::AnnotationType;
+
+    use std::collections::BTreeMap;
+    use std::path::PathBuf;
+
+    fn anchored_annotation(content: &str, line_start: usize, range: (usize, usize)) -> Annotation {
+        Annotation::new_with_metadata(
+            content,
+            BTreeMap::new(),
+            Some((PathBuf::from("src/lib.rs"), line_start, range)),
+            AnnotationType::default(),
+        )
+    }
+
+    #[test]
+    fn reports_when_no_annotations_are_anchored_to_the_file() {
+        let rendered = render_snippet(Path::new("src/lib.rs"), "fn main() {}\n", &[]);
+        assert_eq!(rendered, "src/lib.rs: no annotations anchored to this file.");
+    }
+
+    #[test]
+    fn folds_untouched_lines_between_distant_annotations() {
+        let source = "one\ntwo\nthree\nfour\nfive\nsix\n";
+        let annotations = vec![
+            anchored_annotation("first note", 1, (0, 3)),
+            anchored_annotation("second note", 5, (0, 4)),
+        ];
+
+        let rendered = render_snippet(Path::new("src/lib.rs"), source, &annotations);
+
+        assert!(rendered.contains("one"));
+        assert!(rendered.contains("five"));
+        assert!(rendered.contains("..."));
+        assert!(!rendered.contains("two"));
+        assert!(!rendered.contains("three"));
+        assert!(!rendered.contains("four"));
+        assert!(rendered.contains("first note"));
+        assert!(rendered.contains("second note"));
+    }
+
+    #[test]
+    fn does_not_fold_adjacent_annotated_lines() {
+        let source = "one\ntwo\nthree\n";
+        let annotations = vec![
+            anchored_annotation("first note", 1, (0, 3)),
+            anchored_annotation("second note", 2, (0, 3)),
+        ];
+
+        let rendered = render_snippet(Path::new("src/lib.rs"), source, &annotations);
+
+        assert!(!rendered.contains("..."));
+    }
+
+    #[test]
+    fn converts_a_byte_range_to_a_char_range_across_a_multibyte_prefix() {
+        // "café " is 6 bytes ('é' is 2 bytes) but 5 chars, so a byte range measured past it must
+        // be shifted left by one to land on the right char column.
+        let line = "café bad";
+        assert_eq!(byte_range_to_char_range(line, (6, 9)), (5, 8));
+    }
+
+    #[test]
+    fn aligns_parse_error_carets_to_chars_on_a_multibyte_line() {
+        let source = "café bad\n";
+        // Byte range (6, 9) is the "bad" token; its char range is (5, 8), one column left of the
+        // byte range because of the 2-byte 'é' earlier on the line.
+        let error = ParseError::new((6, 9), "expected `<timestamp> <content>`").with_line(1);
+
+        let rendered = render_parse_errors(Path::new(".annotations"), source, &[error]);
+
+        let caret_line = rendered
+            .lines()
+            .find(|line| line.contains('^'))
+            .expect("rendered output should contain a caret line");
+        let carets_start = caret_line.find('^').expect("caret line should contain a caret");
+
+        assert_eq!(carets_start, caret_line.find("| ").unwrap() + 2 + 5);
+    }
+}
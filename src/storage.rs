@@ -0,0 +1,193 @@
+use crate::annotation::{Annotation, AnnotationsData, ParseError};
+use crate::json;
+
+use std::fs::OpenOptions;
+use std::io::{self, Read, Write};
+use std::path::Path;
+
+/// Reads and writes annotations to a file, in a particular on-disk format.
+///
+/// The `.annotations` line format and the structured `.annotations.json` format both implement
+/// this trait, so [`crate::metadata`] can read and write annotations without caring which one is
+/// backing a given file. [`storage_for`] picks the implementation based on the file's extension.
+pub trait Storage {
+    /// Loads every annotation from `path`, creating it if it doesn't exist yet.
+    ///
+    /// Returns the annotations that parsed successfully, any [`ParseError`]s collected along the
+    /// way, and the raw file contents so a caller can render those errors as a caret-annotated
+    /// snippet.
+    fn load(&self, path: &Path) -> io::Result<(Vec<Annotation>, Vec<ParseError>, String)>;
+
+    /// Overwrites `path` with every annotation in `data`.
+    fn save(&self, path: &Path, data: &AnnotationsData) -> io::Result<()>;
+}
+
+/// The original `<timestamp> <content>` line-oriented format, one annotation per line, with tags,
+/// anchors and levels carried as `\t`-delimited metadata tokens.
+pub struct LineStorage;
+
+/// The structured JSON-array format, selected via a `.json` file extension or `JOTSPOT_FORMAT=json`.
+///
+/// Every field of [`Annotation`] has a dedicated place in the JSON object, so this format has no
+/// need for [`LineStorage`]'s metadata tokens.
+pub struct JsonStorage;
+
+impl Storage for LineStorage {
+    fn load(&self, path: &Path) -> io::Result<(Vec<Annotation>, Vec<ParseError>, String)> {
+        let mut source = String::new();
+
+        OpenOptions::new()
+            .create(true)
+            .write(true)
+            .read(true)
+            .open(path)?
+            .read_to_string(&mut source)?;
+
+        let mut annotations = Vec::new();
+        let mut errors = Vec::new();
+
+        for (index, line) in source.lines().enumerate() {
+            if line.is_empty() {
+                continue;
+            }
+
+            match Annotation::try_from(line) {
+                Ok(annotation) => annotations.push(annotation),
+                Err(error) => errors.push(error.with_line(index + 1)),
+            }
+        }
+
+        Ok((annotations, errors, source))
+    }
+
+    fn save(&self, path: &Path, data: &AnnotationsData) -> io::Result<()> {
+        let mut file = OpenOptions::new()
+            .create(true)
+            .truncate(true)
+            .write(true)
+            .open(path)?;
+
+        let mut content = String::new();
+
+        for annotation in data.get_annotations() {
+            content.push_str(&format!(
+                "{} {}{}\n",
+                annotation.created_at,
+                annotation.serialize_content(),
+                annotation.serialize_metadata()
+            ));
+        }
+
+        writeln!(file, "{}", content)
+    }
+}
+
+impl Storage for JsonStorage {
+    fn load(&self, path: &Path) -> io::Result<(Vec<Annotation>, Vec<ParseError>, String)> {
+        let mut source = String::new();
+
+        OpenOptions::new()
+            .create(true)
+            .write(true)
+            .read(true)
+            .open(path)?
+            .read_to_string(&mut source)?;
+
+        if source.trim().is_empty() {
+            return Ok((Vec::new(), Vec::new(), source));
+        }
+
+        match json::parse_annotations(&source) {
+            Ok(annotations) => Ok((annotations, Vec::new(), source)),
+            Err(message) => {
+                let error = ParseError::new((0, source.len()), message).with_line(1);
+                Ok((Vec::new(), vec![error], source))
+            }
+        }
+    }
+
+    fn save(&self, path: &Path, data: &AnnotationsData) -> io::Result<()> {
+        let mut file = OpenOptions::new()
+            .create(true)
+            .truncate(true)
+            .write(true)
+            .open(path)?;
+
+        write!(file, "{}", json::serialize_annotations(data.get_annotations()))
+    }
+}
+
+/// Picks the [`Storage`] implementation for `path` based on its file extension.
+///
+/// A `.json` extension selects [`JsonStorage`]; anything else falls back to [`LineStorage`], so
+/// existing `.annotations` files keep working unchanged.
+pub fn storage_for(path: &Path) -> Box<dyn Storage> {
+    match path.extension().and_then(|extension| extension.to_str()) {
+        Some("json") => Box::new(JsonStorage),
+        _ => Box::new(LineStorage),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::annotation::AnnotationType;
+
+    use std::collections::BTreeMap;
+    use std::fs;
+
+    fn temp_path(name: &str) -> std::path::PathBuf {
+        let mut path = std::env::temp_dir();
+        path.push(format!("jotspot-storage-test-{}-{}", std::process::id(), name));
+        path
+    }
+
+    #[test]
+    fn migrates_a_legacy_annotations_file_to_json_without_losing_data() {
+        let legacy_path = temp_path("legacy.annotations");
+        let json_path = temp_path("migrated.annotations.json");
+
+        let mut tags = BTreeMap::new();
+        tags.insert("project".to_string(), "jotspot".to_string());
+
+        let annotations = vec![
+            Annotation::new_with_metadata("plain note", BTreeMap::new(), None, AnnotationType::default()),
+            Annotation::new_with_metadata("tagged note", tags, None, AnnotationType::default()),
+            Annotation::new_with_metadata("urgent note", BTreeMap::new(), None, AnnotationType::Error),
+        ];
+
+        let expected: Vec<(u64, String)> = annotations
+            .iter()
+            .map(|annotation| (annotation.created_at, annotation.content.clone()))
+            .collect();
+
+        LineStorage
+            .save(&legacy_path, &AnnotationsData::new(annotations))
+            .expect("should write the legacy file");
+
+        let (legacy_annotations, legacy_errors, _source) = LineStorage
+            .load(&legacy_path)
+            .expect("should read the legacy file back");
+        assert!(legacy_errors.is_empty());
+
+        JsonStorage
+            .save(&json_path, &AnnotationsData::new(legacy_annotations))
+            .expect("should migrate into the JSON file");
+
+        let (migrated_annotations, migrated_errors, _source) = JsonStorage
+            .load(&json_path)
+            .expect("should read the migrated JSON file back");
+        assert!(migrated_errors.is_empty());
+
+        let actual: Vec<(u64, String)> = migrated_annotations
+            .iter()
+            .map(|annotation| (annotation.created_at, annotation.content.clone()))
+            .collect();
+
+        assert_eq!(actual, expected);
+        assert_eq!(migrated_annotations[2].level, AnnotationType::Error);
+
+        fs::remove_file(&legacy_path).ok();
+        fs::remove_file(&json_path).ok();
+    }
+}
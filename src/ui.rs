@@ -1,30 +1,54 @@
-use crate::annotation::{Annotation, AnnotationsData};
+use crate::annotation::{Annotation, AnnotationType, AnnotationsData};
 use crate::metadata;
 
-use cursive::theme::{Effect, Style};
+use cursive::theme::{BaseColor, Color, Effect, Style};
 use cursive::utils::markup::StyledString;
 use cursive::view::{Nameable, Scrollable};
-use cursive::views::{Dialog, LinearLayout, SelectView, TextView};
+use cursive::views::{Dialog, EditView, LinearLayout, SelectView, TextView};
 use cursive::Cursive;
 
-/// Creates a well-formatted string suitable for presentation within the annotation layout.
+/// Picks the [`Style`] used to render an annotation's timestamp/prefix, based on its severity.
 ///
-/// This function creates a [`String`] that represents the formatted text of an [`Annotation`].
-/// The string consists of two elements:
+/// Errors render in red, warnings in yellow, info notes in cyan, and plain notes keep the
+/// terminal's default style.
 ///
-/// 1. A timestamp indicating when the annotation was created, formatted as a human-readable string,
-///    followed by a separator.
+/// # Arguments
+///
+/// - `level`: The severity to style for.
+///
+/// # Returns
+///
+/// The [`Style`] to apply to the timestamp/prefix of an annotation at that severity.
+fn style_for_level(level: AnnotationType) -> Style {
+    match level {
+        AnnotationType::Note => Style::none(),
+        AnnotationType::Info => Style::from(Color::Dark(BaseColor::Cyan)),
+        AnnotationType::Warning => {
+            Style::from(Color::Dark(BaseColor::Yellow)).combine(Effect::Bold)
+        }
+        AnnotationType::Error => Style::from(Color::Dark(BaseColor::Red)).combine(Effect::Bold),
+    }
+}
+
+/// Creates a well-formatted, severity-colored string suitable for presentation within the
+/// annotation layout.
 ///
-/// 2. The textual content of the annotation.
+/// This function creates a [`StyledString`] that represents the formatted text of an
+/// [`Annotation`]. The string consists of:
+///
+/// 1. A timestamp indicating when the annotation was created, formatted as a human-readable string
+///    and colored according to the annotation's severity, followed by a separator.
+///
+/// 2. The textual content of the annotation, followed by its tags if any are present.
 ///
 /// # Arguments
 ///
-/// - `annotation`: A reference to the [`Annotation`] instance that contains the timestamp and content
-///   to be displayed.
+/// - `annotation`: A reference to the [`Annotation`] instance that contains the timestamp, content
+///   and severity to be displayed.
 ///
 /// # Returns
 ///
-/// - A [`String`] containing the formatted text elements.
+/// - A [`StyledString`] containing the formatted text elements.
 ///
 /// # Examples
 ///
@@ -36,12 +60,26 @@ use cursive::Cursive;
 ///
 /// let annotation_text = build_annotation_text(&annotation);
 /// ```
-pub fn build_annotation_text(annotation: &Annotation) -> String {
-    format!(
-        "{:>14} | {}",
-        annotation.format_created_at(),
-        annotation.content
-    )
+pub fn build_annotation_text(annotation: &Annotation) -> StyledString {
+    let mut text = StyledString::styled(
+        format!("{:>14} | ", annotation.format_created_at()),
+        style_for_level(annotation.level),
+    );
+
+    text.append_plain(&annotation.content);
+
+    if !annotation.tags.is_empty() {
+        let tags = annotation
+            .tags
+            .iter()
+            .map(|(key, value)| format!("{}={}", key, value))
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        text.append_plain(format!(" [{}]", tags));
+    }
+
+    text
 }
 
 /// Handles the submission of an annotation.
@@ -53,8 +91,8 @@ pub fn build_annotation_text(annotation: &Annotation) -> String {
 /// # Arguments
 ///
 /// - `s`: A mutable reference to the [`Cursive`] instance.
-/// - `_content`: A reference to the content of the submitted annotation (not used in this function).
-fn on_submit_annotation(s: &mut Cursive, _content: &String) {
+/// - `_index`: The submitted annotation's index into `data` (not used in this function).
+fn on_submit_annotation(s: &mut Cursive, _index: &usize) {
     let dialog = Dialog::text("Would you like to remove the annotation?")
         .title("Annotation")
         .button("No", |s| {
@@ -62,17 +100,25 @@ fn on_submit_annotation(s: &mut Cursive, _content: &String) {
             s.pop_layer();
         })
         .button("Yes", |s| {
-            let mut select_view = s
-                .find_name::<SelectView>("annotation_list")
-                .expect("It there must be a select view named `annotation_list`");
+            let data_index = s
+                .find_name::<SelectView<usize>>("annotation_list")
+                .expect("It there must be a select view named `annotation_list`")
+                .selection()
+                .map(|value| *value);
+
+            if let Some(data_index) = data_index {
+                let data = s
+                    .user_data::<AnnotationsData>()
+                    .expect("It there must be a user data");
 
-            let data = s
-                .user_data::<AnnotationsData>()
-                .expect("It there must be a user data");
+                data.get_annotations_mut().remove(data_index);
 
-            if let Some(selected_id) = select_view.selected_id() {
-                select_view.remove_item(selected_id);
-                data.get_annotations_mut().remove(selected_id);
+                // `SelectView::remove_item` only drops the view's own row; it never renumbers the
+                // indices stored on the remaining items, so a one-shot removal leaves them stale
+                // (and a second deletion can then index past the end of `data`). Rebuilding the
+                // whole view from `data`, the same way the tag filter does, keeps every stored
+                // index correct no matter how many removals happen in a row.
+                filter_annotation_list_by_tag(s, "");
             }
 
             // Close the dialog.
@@ -127,8 +173,9 @@ pub fn build_annotations_layout(annotations: &[Annotation]) -> Dialog {
     } else {
         let select_view = annotations
             .iter()
-            .fold(SelectView::new(), |select_view, annotation| {
-                select_view.item_str(build_annotation_text(annotation))
+            .enumerate()
+            .fold(SelectView::new(), |select_view, (index, annotation)| {
+                select_view.item(build_annotation_text(annotation), index)
             })
             .on_submit(on_submit_annotation)
             .with_name("annotation_list");
@@ -137,6 +184,112 @@ pub fn build_annotations_layout(annotations: &[Annotation]) -> Dialog {
     }
 }
 
+/// Ranks an [`AnnotationType`] so the most severe levels group first in the `SelectView`.
+///
+/// # Arguments
+///
+/// - `level`: The severity to rank.
+///
+/// # Returns
+///
+/// A `u8` where higher values indicate higher severity.
+fn severity_rank(level: AnnotationType) -> u8 {
+    match level {
+        AnnotationType::Error => 3,
+        AnnotationType::Warning => 2,
+        AnnotationType::Info => 1,
+        AnnotationType::Note => 0,
+    }
+}
+
+/// Rebuilds the `annotation_list` [`SelectView`] to show only annotations carrying `tag_key`, or
+/// every annotation when `tag_key` is empty.
+///
+/// This backs the tag filter prompt opened with the `/` key in [`run_annotate_tui`].
+///
+/// # Arguments
+///
+/// - `s`: A mutable reference to the [`Cursive`] instance.
+/// - `tag_key`: The tag key to filter by, or an empty string to clear the filter.
+fn filter_annotation_list_by_tag(s: &mut Cursive, tag_key: &str) {
+    let data = s
+        .user_data::<AnnotationsData>()
+        .expect("It there must be a user data");
+
+    let items: Vec<(StyledString, usize)> = data
+        .get_annotations()
+        .iter()
+        .enumerate()
+        .filter(|(_, annotation)| tag_key.is_empty() || annotation.tags.contains_key(tag_key))
+        .map(|(index, annotation)| (build_annotation_text(annotation), index))
+        .collect();
+
+    let mut select_view = s
+        .find_name::<SelectView<usize>>("annotation_list")
+        .expect("It there must be a select view named `annotation_list`");
+
+    select_view.clear();
+    for (label, value) in items {
+        select_view.add_item(label, value);
+    }
+}
+
+/// Rebuilds the `annotation_list` [`SelectView`] sorted by severity, most severe first.
+///
+/// This backs the sort-by-level action bound to the `l` key in [`run_annotate_tui`]; pressing it
+/// again sorts again rather than restoring the previous order. Like
+/// [`filter_annotation_list_by_tag`], it re-enumerates `data` from scratch, so the index stored on
+/// each item is always the annotation's current, real position — it can't go stale, since
+/// [`on_submit_annotation`] rebuilds the view the same way after every removal.
+///
+/// # Arguments
+///
+/// - `s`: A mutable reference to the [`Cursive`] instance.
+fn sort_annotation_list_by_level(s: &mut Cursive) {
+    let data = s
+        .user_data::<AnnotationsData>()
+        .expect("It there must be a user data");
+
+    let mut items: Vec<(StyledString, usize, AnnotationType)> = data
+        .get_annotations()
+        .iter()
+        .enumerate()
+        .map(|(index, annotation)| {
+            (build_annotation_text(annotation), index, annotation.level)
+        })
+        .collect();
+
+    items.sort_by_key(|(_, _, level)| std::cmp::Reverse(severity_rank(*level)));
+
+    let mut select_view = s
+        .find_name::<SelectView<usize>>("annotation_list")
+        .expect("It there must be a select view named `annotation_list`");
+
+    select_view.clear();
+    for (label, value, _) in items {
+        select_view.add_item(label, value);
+    }
+}
+
+/// Opens a dialog prompting for a tag key, then filters the `annotation_list` to only the
+/// annotations carrying that tag. Submitting an empty key clears the filter.
+///
+/// # Arguments
+///
+/// - `s`: A mutable reference to the [`Cursive`] instance.
+fn prompt_tag_filter(s: &mut Cursive) {
+    let dialog = Dialog::around(EditView::new().on_submit(|s, tag_key| {
+        filter_annotation_list_by_tag(s, tag_key);
+        s.pop_layer();
+    }).with_name("tag_filter_input"))
+    .title("Filter by tag key")
+    .button("Cancel", |s| {
+        s.pop_layer();
+    });
+
+    s.add_layer(dialog);
+}
+
 /// Runs the Text User Interface (TUI) for annotating and displaying a list of annotations.
 ///
 /// This function initializes and runs a Cursive-based Text User Interface (TUI) to interactively
@@ -170,6 +323,8 @@ pub fn run_annotate_tui(annotations: Vec<Annotation>) {
 
     siv.set_user_data(AnnotationsData::new(annotations));
     siv.add_layer(annotations_layout);
+    siv.add_global_callback('/', prompt_tag_filter);
+    siv.add_global_callback('l', sort_annotation_list_by_level);
     siv.run();
 
     metadata::save_annotations(siv.user_data().expect("It there must be a data"));